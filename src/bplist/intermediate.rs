@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+use super::reference_table::ReferenceTable;
+use super::result::{Error, Result};
+use super::trailer::Trailer;
+use super::{current_pos, load_ascii_str, load_data, load_date, load_int, load_real, load_single,
+            load_uid, load_utf16_str, marker, util, BPList};
+
+/// A `BPList` object as pass one parses it: scalars are fully decoded, but array/dict children are
+/// left as object-table indices rather than recursed into. Pass two (`materialize`) resolves those
+/// indices against the rest of the table, which is what lets it memoize shared subtrees and detect
+/// cycles without ever re-reading the file.
+pub(super) enum RawEntry {
+    Scalar(BPList),
+    Array(Vec<u64>),
+    Set(Vec<u64>),
+    Dict(Vec<(u64, u64)>),
+}
+
+/// Pass one: reads each of `trailer.num_objects` objects exactly once, keyed by its object index,
+/// by seeking to its offset in `reference_table`. A plist that shares one subtree across many
+/// references is therefore parsed once no matter how many places point at it.
+pub fn load_all<R: Read + Seek>(
+    file: &mut R,
+    trailer: &Trailer,
+    reference_table: &ReferenceTable,
+    lossy: bool,
+) -> Result<HashMap<u64, RawEntry>> {
+    let mut table = HashMap::with_capacity(trailer.num_objects as usize);
+    for index in 0..trailer.num_objects {
+        let offset = reference_table.get(&index).ok_or(Error::NotFound)?;
+        file.seek(SeekFrom::Start(offset))?;
+        table.insert(index, load_entry(file, trailer, lossy)?);
+    }
+    Ok(table)
+}
+
+fn load_entry<R: Read + Seek>(file: &mut R, trailer: &Trailer, lossy: bool) -> Result<RawEntry> {
+    let offset = current_pos(file)?;
+    let mut marker_byte = [0u8];
+    let bytes_read = file.read(&mut marker_byte)?;
+    if bytes_read == 0 {
+        return Err(Error::UnexpectedEof { offset, needed: 1 });
+    }
+
+    let marker_high = (marker_byte[0] & 0b11110000) >> 4;
+    let marker_low = marker_byte[0] & 0b00001111;
+
+    match marker_high {
+        marker::SINGLE => load_single(marker_low).map(RawEntry::Scalar),
+        marker::INT => load_int(file, marker_low).map(RawEntry::Scalar),
+        marker::REAL => load_real(file, marker_low).map(RawEntry::Scalar),
+        marker::DATE => load_date(file, marker_low).map(RawEntry::Scalar),
+        marker::DATA => load_data(file, trailer, marker_low).map(RawEntry::Scalar),
+        marker::ASCII_STR => {
+            load_ascii_str(file, trailer, marker_low, lossy).map(RawEntry::Scalar)
+        }
+        marker::UTF16_STR => {
+            load_utf16_str(file, trailer, marker_low, lossy).map(RawEntry::Scalar)
+        }
+        marker::UID => load_uid(file, marker_low).map(RawEntry::Scalar),
+
+        marker::ARRAY => load_array_refs(file, trailer, marker_low, lossy).map(RawEntry::Array),
+        marker::SET => load_array_refs(file, trailer, marker_low, lossy).map(RawEntry::Set),
+        marker::DICT => load_dict_refs(file, trailer, marker_low, lossy).map(RawEntry::Dict),
+
+        _ => Err(Error::BadMarker {
+            offset,
+            marker: marker_byte[0],
+        }),
+    }
+}
+
+fn load_array_refs<R: Read + Seek>(
+    file: &mut R,
+    trailer: &Trailer,
+    marker_low: u8,
+    lossy: bool,
+) -> Result<Vec<u64>> {
+    let length = load_length(file, trailer, marker_low, lossy)?;
+
+    let mut ref_buf = vec![0; trailer.object_ref_size as usize];
+    let mut refs = Vec::with_capacity(length as usize);
+    for _ in 0..length {
+        file.read_exact(ref_buf.as_mut_slice())?;
+        refs.push(read_objref(file, trailer, &ref_buf)?);
+    }
+    Ok(refs)
+}
+
+fn load_dict_refs<R: Read + Seek>(
+    file: &mut R,
+    trailer: &Trailer,
+    marker_low: u8,
+    lossy: bool,
+) -> Result<Vec<(u64, u64)>> {
+    let length = load_length(file, trailer, marker_low, lossy)?;
+
+    let mut ref_buf = vec![0; trailer.object_ref_size as usize];
+    let mut keyrefs = Vec::with_capacity(length as usize);
+    for _ in 0..length {
+        file.read_exact(ref_buf.as_mut_slice())?;
+        keyrefs.push(read_objref(file, trailer, &ref_buf)?);
+    }
+
+    let mut objrefs = Vec::with_capacity(length as usize);
+    for _ in 0..length {
+        file.read_exact(ref_buf.as_mut_slice())?;
+        objrefs.push(read_objref(file, trailer, &ref_buf)?);
+    }
+
+    Ok(keyrefs.into_iter().zip(objrefs).collect())
+}
+
+/// Decodes an objref/keyref just read into `ref_buf` and checks it actually points somewhere in
+/// the object table -- a malformed plist can claim a ref past `num_objects`, which would otherwise
+/// only surface as a confusing `NotFound` once `materialize` tries to resolve it.
+fn read_objref<R: Seek>(file: &mut R, trailer: &Trailer, ref_buf: &[u8]) -> Result<u64> {
+    let objref = util::from_be_bytes_unsigned(ref_buf)?;
+    if objref >= trailer.num_objects {
+        return Err(Error::RefOutOfRange {
+            offset: current_pos(file)?,
+            objref,
+        });
+    }
+    Ok(objref)
+}
+
+fn load_length<R: Read + Seek>(
+    file: &mut R,
+    trailer: &Trailer,
+    marker_low: u8,
+    lossy: bool,
+) -> Result<i64> {
+    if marker_low != 0b1111 {
+        return Ok(marker_low as i64);
+    }
+
+    let offset = current_pos(file)?;
+    match load_entry(file, trailer, lossy)? {
+        RawEntry::Scalar(BPList::Int(n)) => {
+            i64::try_from(n).map_err(|_| Error::BadLength { offset, width: 0 })
+        }
+        _ => Err(Error::BadLength { offset, width: 0 }),
+    }
+}
+
+/// Pass two: resolves `index` against `table`, materializing shared references as a single
+/// `Rc<BPList>` via `cache` and rejecting a re-entrant index in `on_stack` as a reference cycle
+/// instead of recursing forever.
+pub fn materialize(
+    table: &HashMap<u64, RawEntry>,
+    index: u64,
+    cache: &mut HashMap<u64, Rc<BPList>>,
+    on_stack: &mut HashSet<u64>,
+) -> Result<Rc<BPList>> {
+    if let Some(cached) = cache.get(&index) {
+        return Ok(Rc::clone(cached));
+    }
+
+    if !on_stack.insert(index) {
+        return Err(Error::InvalidFormat("reference cycle"));
+    }
+
+    let entry = table.get(&index).ok_or(Error::NotFound)?;
+    let value = match entry {
+        RawEntry::Scalar(value) => value.clone(),
+        RawEntry::Array(items) => {
+            let items = items
+                .iter()
+                .map(|&idx| materialize(table, idx, cache, on_stack))
+                .collect::<Result<Vec<_>>>()?;
+            BPList::Array(items)
+        }
+        RawEntry::Set(items) => {
+            let items = items
+                .iter()
+                .map(|&idx| materialize(table, idx, cache, on_stack))
+                .collect::<Result<Vec<_>>>()?;
+            BPList::Set(items)
+        }
+        RawEntry::Dict(pairs) => {
+            let pairs = pairs
+                .iter()
+                .map(|&(keyref, objref)| {
+                    let key = materialize(table, keyref, cache, on_stack)?;
+                    let value = materialize(table, objref, cache, on_stack)?;
+                    Ok((key, value))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            BPList::Dict(pairs)
+        }
+    };
+
+    on_stack.remove(&index);
+
+    let value = Rc::new(value);
+    cache.insert(index, Rc::clone(&value));
+    Ok(value)
+}
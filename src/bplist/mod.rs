@@ -1,14 +1,21 @@
+mod intermediate;
+pub mod pos_reader;
 mod reference_table;
 mod result;
+pub mod stream_decode;
 mod trailer;
-mod util;
+pub mod util;
+pub mod writer;
 
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
 use std::str;
 
+use pos_reader::PosReader;
 use reference_table::ReferenceTable;
 pub use result::{Error, Result};
 use trailer::Trailer;
@@ -57,19 +64,20 @@ TRAILER
 
 */
 
+#[derive(Clone)]
 pub enum BPList {
     Null,
     Bool(bool),
     Filler,
-    Int(i64),
+    Int(i128),
     Real(f64),
-    // Date
+    Date(f64),
     Data(Vec<u8>),
     Str(String),
     UID(Vec<u8>),
-    Array(Vec<Box<BPList>>),
-    // Set
-    Dict(Vec<(Box<BPList>, Box<BPList>)>),
+    Array(Vec<Rc<BPList>>),
+    Set(Vec<Rc<BPList>>),
+    Dict(Vec<(Rc<BPList>, Rc<BPList>)>),
 }
 
 impl Debug for BPList {
@@ -87,21 +95,45 @@ impl PartialEq for BPList {
             (Bool(b1), Bool(b2)) => b1 == b2,
             (Filler, Filler) => true,
             (Int(i1), Int(i2)) => i1 == i2,
+            (Date(d1), Date(d2)) => d1 == d2,
             (Data(d1), Data(d2)) => d1 == d2,
             (Str(s1), Str(s2)) => s1 == s2,
             (UID(u1), UID(u2)) => u1 == u2,
 
-            // assume no equality for: Real, Array, Dict.
+            // assume no equality for: Real, Array, Set, Dict.
             _ => false,
         }
     }
 }
 
 impl BPList {
+    /// Convenience entry point for the common case of decoding a plist from an open file. Use
+    /// `from_reader` directly to decode from any other `Read + Seek` source.
     pub fn load(file: &mut File) -> Result<BPList> {
+        BPList::from_reader(file)
+    }
+
+    /// Decodes a binary plist from any `Read + Seek` source: an open `File`, a `Cursor` over an
+    /// in-memory buffer, or anything else that can be read and seeked. String fields with
+    /// malformed encoding abort the decode with `Error::EncodingError`/`Error::InvalidUtf16`; use
+    /// `from_reader_lossy` to recover partial data instead.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> Result<BPList> {
+        Self::decode(reader, false)
+    }
+
+    /// Like `from_reader`, but malformed `Str` bytes are decoded with U+FFFD substituted for
+    /// invalid sequences (see `BPList::as_utf8_lossy`/`as_utf16_lossy`) instead of failing the
+    /// whole decode.
+    pub fn from_reader_lossy<R: Read + Seek>(reader: R) -> Result<BPList> {
+        Self::decode(reader, true)
+    }
+
+    fn decode<R: Read + Seek>(reader: R, lossy: bool) -> Result<BPList> {
+        let mut reader = PosReader::new(reader)?;
+
         // ensuring this is the right format
         let mut magic_buf = [0; 8];
-        file.read_exact(&mut magic_buf)?;
+        reader.read_exact(&mut magic_buf)?;
         let magic_buf_str = match str::from_utf8(&magic_buf) {
             Err(_) => return Err(Error::EncodingError),
             Ok(x) => x,
@@ -110,55 +142,40 @@ impl BPList {
             return Err(Error::InvalidFormat("invalid magic string"));
         }
 
-        // get the necessary information to load the object table
-        let object_table_pos = file.seek(SeekFrom::Current(0))?;
-
-        file.seek(SeekFrom::End(-32))?;
-        let trailer = Trailer::load(file)?;
-
-        file.seek(SeekFrom::Start(trailer.offset_table_start))?;
-        let reference_table = ReferenceTable::load(file, &trailer)?;
-
-        // recursively populating the value
-        file.seek(SeekFrom::Start(object_table_pos))?;
-        BPList::load_item(file, &trailer, &reference_table)
+        reader.seek(SeekFrom::End(-32))?;
+        let trailer = Trailer::load(&mut reader)?;
+
+        reader.seek(SeekFrom::Start(trailer.offset_table_start))?;
+        let reference_table = ReferenceTable::load(&mut reader, &trailer)?;
+
+        // pass one: parse every object exactly once, keyed by object index, recording
+        // array/dict children as indices rather than recursing into them.
+        let table = intermediate::load_all(&mut reader, &trailer, &reference_table, lossy)?;
+
+        // pass two: materialize the tree from the top object, resolving indices against
+        // `table` so a shared subtree is only ever allocated once and a reference cycle is
+        // rejected instead of overflowing the stack.
+        let mut cache = HashMap::new();
+        let mut on_stack = HashSet::new();
+        let root = intermediate::materialize(
+            &table,
+            trailer.top_object_offset,
+            &mut cache,
+            &mut on_stack,
+        )?;
+        Ok((*root).clone())
     }
 
-    fn load_item(
-        file: &mut File,
-        trailer: &Trailer,
-        reference_table: &ReferenceTable,
-    ) -> Result<BPList> {
-        let mut marker = [0u8];
-        let bytes_read = file.read(&mut marker)?;
-        if bytes_read == 0 {
-            return Err(Error::EOF);
-        }
+    /// Convenience wrapper around `from_reader` for decoding a plist already sitting in memory,
+    /// e.g. bytes read from a network buffer or embedded inside a larger container.
+    pub fn from_bytes(bytes: &[u8]) -> Result<BPList> {
+        BPList::from_reader(std::io::Cursor::new(bytes))
+    }
 
-        let marker_high = (marker[0] & 0b11110000) >> 4;
-        let marker_low = marker[0] & 0b00001111;
-
-        match marker_high {
-            // simple types
-            marker::SINGLE => load_single(marker_low),
-            marker::INT => load_int(file, marker_low),
-            marker::REAL => load_real(file, marker_low),
-            marker::DATE => todo!("date"),
-            marker::DATA => load_data(file, trailer, reference_table, marker_low),
-            marker::ASCII_STR => load_ascii_str(file, trailer, reference_table, marker_low),
-            marker::UTF16_STR => load_utf16_str(file, trailer, reference_table, marker_low),
-            marker::UID => load_uid(file, marker_low),
-
-            // complex types
-            marker::ARRAY => load_array(file, trailer, reference_table, marker_low),
-            marker::SET => todo!("set"),
-            marker::DICT => load_dict(file, trailer, reference_table, marker_low),
-
-            x => {
-                println!("{}", x);
-                Err(Error::InvalidFormat("unrecognized part"))
-            }
-        }
+    /// Convenience wrapper around `from_reader_lossy` for decoding a plist already sitting in
+    /// memory.
+    pub fn from_bytes_lossy(bytes: &[u8]) -> Result<BPList> {
+        BPList::from_reader_lossy(std::io::Cursor::new(bytes))
     }
 
     pub fn print(&self, fmt: &mut Formatter, depth: u64) -> fmt::Result {
@@ -168,6 +185,7 @@ impl BPList {
             BPList::Filler => write!(fmt, "filler"),
             BPList::Int(i) => write!(fmt, "{}", i),
             BPList::Real(i) => write!(fmt, "{}", i),
+            BPList::Date(seconds) => write!(fmt, "{}", format_apple_date(*seconds)),
             BPList::Data(bytes) => {
                 write!(fmt, "[ ")?;
                 for byte in bytes.into_iter() {
@@ -194,6 +212,17 @@ impl BPList {
                 print_depth(fmt, depth)?;
                 write!(fmt, "]")
             }
+            BPList::Set(set) => {
+                writeln!(fmt, "{{ ")?;
+
+                for item in set.into_iter() {
+                    print_depth(fmt, depth + 1)?;
+                    item.print(fmt, depth + 1)?;
+                    writeln!(fmt, ",")?;
+                }
+                print_depth(fmt, depth)?;
+                write!(fmt, "}}")
+            }
             BPList::Dict(array) => {
                 writeln!(fmt, "{{")?;
 
@@ -234,10 +263,61 @@ impl BPList {
     }
 
     pub fn geti<'a>(&'a self, lookup_key: usize) -> Result<&'a BPList> {
-        self.get(BPList::Int(lookup_key as i64))
+        self.get(BPList::Int(lookup_key as i128))
+    }
+
+    /// Returns this `Int`'s value as an `i128`, the widest type a 16-byte bplist integer can need,
+    /// or `None` if this isn't an `Int`.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            BPList::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns this `Int`'s value as an `i64`, or `None` if this isn't an `Int` or the value
+    /// overflows `i64` (as a 16-byte field storing a magnitude beyond `i64::MAX` can).
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_i128().and_then(|n| i64::try_from(n).ok())
+    }
+
+    /// Returns this `Int`'s value as a `u64`, or `None` if this isn't an `Int`, is negative, or
+    /// overflows `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_i128().and_then(|n| u64::try_from(n).ok())
+    }
+
+    /// Returns the Unix-epoch equivalent of a `Date` object, or `None` if this isn't one.
+    pub fn as_unix_timestamp(&self) -> Option<f64> {
+        match self {
+            BPList::Date(apple_seconds) => Some(apple_seconds + APPLE_EPOCH_OFFSET),
+            _ => None,
+        }
+    }
+
+    /// Serializes this tree back into the `bplist00` binary format.
+    pub fn write<W: Write + Seek>(&self, w: &mut W) -> Result<()> {
+        writer::write(self, w)
+    }
+
+    /// Convenience wrapper around `write` for callers that just want the encoded bytes in memory.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        self.write(&mut buf)?;
+        Ok(buf.into_inner())
     }
 }
 
+/// Seconds between the Unix epoch (1970-01-01) and the Apple reference date (2001-01-01) used by
+/// `NSDate`/`CFDate`.
+const APPLE_EPOCH_OFFSET: f64 = 978307200.0;
+
+/// The current byte offset of `file`, for annotating a decode error with exactly where it
+/// happened.
+pub(crate) fn current_pos<R: Seek>(file: &mut R) -> Result<u64> {
+    Ok(file.seek(SeekFrom::Current(0))?)
+}
+
 fn print_depth(fmt: &mut Formatter, depth: u64) -> fmt::Result {
     for _ in 0..depth {
         write!(fmt, "  ")?;
@@ -257,29 +337,48 @@ fn load_single(marker_low: u8) -> Result<BPList> {
     })
 }
 
-fn load_int(file: &mut File, marker_low: u8) -> Result<BPList> {
+/// Reads an `Int` object. The byte width is `2^marker_low`: widths below 8 hold a plain unsigned
+/// magnitude, a width of 8 holds a signed two's-complement `i64` (as Apple's writer emits), and a
+/// width of 16 holds a full signed two's-complement `i128` -- the form Apple uses to store
+/// unsigned values that don't fit in an `i64`, with the magnitude sitting in the low 8 bytes and
+/// the high 8 bytes zeroed.
+fn load_int<R: Read + Seek>(file: &mut R, marker_low: u8) -> Result<BPList> {
     let mut byte_count = 1usize;
     for _ in 0..marker_low {
         byte_count *= 2;
     }
 
+    if byte_count > 16 {
+        return Err(Error::BadLength {
+            offset: current_pos(file)?,
+            width: byte_count,
+        });
+    }
+
     let mut bytes = vec![0; byte_count];
     file.read_exact(bytes.as_mut_slice())?;
 
-    let mut n = 0i64;
-    for byte in bytes.into_iter() {
-        n = (n << 8) | (byte as i64);
-    }
+    let n: i128 = match byte_count {
+        16 | 8 => util::from_be_bytes_signed(&bytes)?,
+        _ => util::from_be_bytes_unsigned(&bytes)? as i128,
+    };
 
     Ok(BPList::Int(n))
 }
 
-fn load_real(file: &mut File, marker_low: u8) -> Result<BPList> {
+fn load_real<R: Read + Seek>(file: &mut R, marker_low: u8) -> Result<BPList> {
     let mut byte_count = 1usize;
     for _ in 0..marker_low {
         byte_count *= 2;
     }
 
+    if byte_count > 8 {
+        return Err(Error::BadLength {
+            offset: current_pos(file)?,
+            width: byte_count,
+        });
+    }
+
     let mut bytes = vec![0; byte_count];
     file.read_exact(bytes.as_mut_slice())?;
 
@@ -296,132 +395,139 @@ fn load_real(file: &mut File, marker_low: u8) -> Result<BPList> {
     Ok(BPList::Real(f64::from_be_bytes(float_buf)))
 }
 
-fn load_data(
-    file: &mut File,
-    trailer: &Trailer,
-    reference_table: &ReferenceTable,
-    marker_low: u8,
-) -> Result<BPList> {
-    let length = load_length(file, trailer, reference_table, marker_low)?;
+fn load_date<R: Read>(file: &mut R, marker_low: u8) -> Result<BPList> {
+    if marker_low != 0b0011 {
+        return Err(Error::InvalidFormat("unrecognized date width"));
+    }
+
+    let mut float_buf = [0u8; 8];
+    file.read_exact(&mut float_buf)?;
+    let seconds = f64::from_be_bytes(float_buf);
+
+    if seconds.is_nan() || seconds.is_infinite() {
+        return Err(Error::InvalidFormat("infinite or nan date"));
+    }
+
+    Ok(BPList::Date(seconds))
+}
+
+/// Renders Apple-epoch seconds as an ISO-8601-ish `YYYY-MM-DDTHH:MM:SSZ` string, falling back to
+/// the raw offset for values outside the range a civil calendar can represent.
+fn format_apple_date(apple_seconds: f64) -> String {
+    let unix_seconds = apple_seconds + APPLE_EPOCH_OFFSET;
+    let days = (unix_seconds / 86400.0).floor() as i64;
+    let mut secs_of_day = (unix_seconds - (days as f64) * 86400.0) as i64;
+    if secs_of_day < 0 {
+        secs_of_day += 86400;
+    }
+
+    let (year, month, day) = civil_from_days(days);
+    let hour = secs_of_day / 3600;
+    let minute = (secs_of_day % 3600) / 60;
+    let second = secs_of_day % 60;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// Converts a day count since the Unix epoch into a (year, month, day) civil date.
+/// Algorithm from Howard Hinnant's `civil_from_days`.
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = (z - era * 146097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = if month <= 2 { y + 1 } else { y };
+    (year, month, day)
+}
+
+fn load_data<R: Read + Seek>(file: &mut R, trailer: &Trailer, marker_low: u8) -> Result<BPList> {
+    let length = load_length(file, trailer, marker_low)?;
     let mut buf = vec![0; length as usize];
     file.read_exact(buf.as_mut_slice())?;
     Ok(BPList::Data(buf))
 }
 
-fn load_ascii_str(
-    file: &mut File,
+fn load_ascii_str<R: Read + Seek>(
+    file: &mut R,
     trailer: &Trailer,
-    reference_table: &ReferenceTable,
     marker_low: u8,
+    lossy: bool,
 ) -> Result<BPList> {
-    let length = load_length(file, trailer, reference_table, marker_low)?;
+    let length = load_length(file, trailer, marker_low)?;
     let mut buf = vec![0; length as usize];
     file.read_exact(buf.as_mut_slice())?;
-    Ok(BPList::Str(util::as_utf8(&buf)?.to_owned()))
+    let s = if lossy {
+        util::as_utf8_lossy(&buf)
+    } else {
+        util::as_utf8(&buf)?.to_owned()
+    };
+    Ok(BPList::Str(s))
 }
 
-fn load_utf16_str(
-    file: &mut File,
+fn load_utf16_str<R: Read + Seek>(
+    file: &mut R,
     trailer: &Trailer,
-    reference_table: &ReferenceTable,
     marker_low: u8,
+    lossy: bool,
 ) -> Result<BPList> {
-    let length = load_length(file, trailer, reference_table, marker_low)?;
+    let length = load_length(file, trailer, marker_low)?;
+    let offset = current_pos(file)?;
     let mut buf = vec![0; length as usize * 2];
     file.read_exact(buf.as_mut_slice())?;
-    Ok(BPList::Str(util::as_utf16(&buf)?))
+    let s = if lossy {
+        util::as_utf16_lossy(&buf)
+    } else {
+        util::as_utf16be(&buf).map_err(|_| Error::InvalidUtf16 { offset })?
+    };
+    Ok(BPList::Str(s))
 }
 
-fn load_uid(file: &mut File, marker_low: u8) -> Result<BPList> {
+fn load_uid<R: Read>(file: &mut R, marker_low: u8) -> Result<BPList> {
     let mut buf = vec![0; (marker_low + 1) as usize];
     file.read_exact(buf.as_mut_slice())?;
     Ok(BPList::UID(buf))
 }
 
-fn load_array(
-    file: &mut File,
-    trailer: &Trailer,
-    reference_table: &ReferenceTable,
-    marker_low: u8,
-) -> Result<BPList> {
-    let length = load_length(file, trailer, reference_table, marker_low)?;
-
-    let mut ref_buf = vec![0; trailer.object_ref_size as usize];
-    let mut refs = Vec::new();
-    for _ in 0..length {
-        file.read_exact(ref_buf.as_mut_slice())?;
-        refs.push(util::from_be_bytes(&ref_buf));
-    }
-
-    let mut objs = Vec::new();
-    for objref in refs.into_iter() {
-        seek_ref(file, reference_table, objref)?;
-        objs.push(Box::new(BPList::load_item(file, trailer, reference_table)?));
-    }
-
-    Ok(BPList::Array(objs))
-}
-
-fn load_dict(
-    file: &mut File,
-    trailer: &Trailer,
-    reference_table: &ReferenceTable,
-    marker_low: u8,
-) -> Result<BPList> {
-    let length = load_length(file, trailer, reference_table, marker_low)?;
-
-    let mut ref_buf = vec![0; trailer.object_ref_size as usize];
-    let mut keyrefs = Vec::new();
-    let mut objrefs = Vec::new();
-    for _ in 0..length {
-        file.read_exact(ref_buf.as_mut_slice())?;
-        let keyref = util::from_be_bytes(&ref_buf);
-        keyrefs.push(keyref);
+/// Reads an inline length: either the marker's low nibble directly, or (when that nibble is the
+/// `0b1111` escape) the `Int` object that immediately follows it.
+fn load_length<R: Read + Seek>(file: &mut R, _trailer: &Trailer, marker_low: u8) -> Result<i64> {
+    if marker_low != 0b1111 {
+        return Ok(marker_low as i64);
     }
 
-    for _ in 0..length {
-        file.read_exact(ref_buf.as_mut_slice())?;
-        let objref = util::from_be_bytes(&ref_buf);
-        objrefs.push(objref);
+    let offset = current_pos(file)?;
+    let mut marker_byte = [0u8];
+    let bytes_read = file.read(&mut marker_byte)?;
+    if bytes_read == 0 {
+        return Err(Error::UnexpectedEof { offset, needed: 1 });
     }
 
-    let mut objs = Vec::new();
-    for (keyref, objref) in keyrefs.into_iter().zip(objrefs.into_iter()) {
-        seek_ref(file, reference_table, keyref)?;
-        let key = BPList::load_item(file, trailer, reference_table)?;
-
-        seek_ref(file, reference_table, objref)?;
-        let obj = BPList::load_item(file, trailer, reference_table)?;
-
-        objs.push((Box::new(key), Box::new(obj)));
+    let marker_high = (marker_byte[0] & 0b11110000) >> 4;
+    let inner_low = marker_byte[0] & 0b00001111;
+    if marker_high != marker::INT {
+        return Err(Error::BadLength {
+            offset,
+            width: inner_low as usize,
+        });
     }
 
-    Ok(BPList::Dict(objs))
-}
-
-fn load_length(
-    file: &mut File,
-    trailer: &Trailer,
-    reference_table: &ReferenceTable,
-    marker_low: u8,
-) -> Result<i64> {
-    if marker_low == 0b1111 {
-        let item = BPList::load_item(file, trailer, reference_table)?;
-        if let BPList::Int(n) = item {
-            Ok(n)
-        } else {
-            Err(Error::InvalidFormat("invalid dict size"))
-        }
-    } else {
-        Ok(marker_low as i64)
+    match load_int(file, inner_low)? {
+        BPList::Int(n) => i64::try_from(n).map_err(|_| Error::BadLength {
+            offset,
+            width: inner_low as usize,
+        }),
+        _ => unreachable!("load_int always returns BPList::Int"),
     }
 }
 
-fn seek_ref(file: &mut File, reference_table: &ReferenceTable, objref: u64) -> Result<u64> {
-    let offset = reference_table.get(&objref).ok_or(Error::NotFound)?;
-    Ok(file.seek(SeekFrom::Start(offset))?)
-}
-
 mod marker {
     pub const SINGLE: u8 = 0;
     pub const INT: u8 = 1;
@@ -435,3 +541,86 @@ mod marker {
     pub const SET: u8 = 12;
     pub const DICT: u8 = 13;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_round_trips_past_i64_via_16_byte_width() {
+        let beyond_i64_max = i64::MAX as i128 + 1;
+        let value = BPList::Int(beyond_i64_max);
+        let bytes = value.to_bytes().unwrap();
+        let decoded = BPList::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.as_i128(), Some(beyond_i64_max));
+        assert_eq!(decoded.as_u64(), Some(beyond_i64_max as u64));
+        assert_eq!(decoded.as_i64(), None);
+
+        let beyond_i64_min = i64::MIN as i128 - 1;
+        let decoded = BPList::from_bytes(&BPList::Int(beyond_i64_min).to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.as_i128(), Some(beyond_i64_min));
+        assert_eq!(decoded.as_i64(), None);
+        assert_eq!(decoded.as_u64(), None);
+    }
+
+    #[test]
+    fn as_i64_and_as_u64_reflect_values_within_range() {
+        assert_eq!(BPList::Int(-5).as_i64(), Some(-5));
+        assert_eq!(BPList::Int(-5).as_u64(), None);
+        assert_eq!(BPList::Int(5).as_u64(), Some(5));
+        assert_eq!(BPList::Null.as_i64(), None);
+    }
+
+    /// A hand-built `bplist00` whose only object is a 1-element array referencing itself --
+    /// malformed input the writer can never produce, so this is crafted directly rather than via
+    /// `BPList::to_bytes`.
+    #[test]
+    fn materialize_rejects_a_reference_cycle() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"bplist00");
+        // object 0: ARRAY, length 1, containing one objref (1 byte wide) pointing at itself.
+        bytes.push((marker::ARRAY << 4) | 0b0001);
+        bytes.push(0x00);
+        // offset table: one entry (1 byte wide) pointing at object 0's offset.
+        bytes.push(8);
+        // trailer: 6 unused bytes, offset_table_offset_size=1, object_ref_size=1,
+        // num_objects=1, top_object_offset=0, offset_table_start=10.
+        bytes.extend_from_slice(&[0u8; 6]);
+        bytes.push(1);
+        bytes.push(1);
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        bytes.extend_from_slice(&0u64.to_be_bytes());
+        bytes.extend_from_slice(&10u64.to_be_bytes());
+
+        match BPList::from_bytes(&bytes) {
+            Err(Error::InvalidFormat("reference cycle")) => {}
+            other => panic!("expected a reference-cycle error, got {:?}", other),
+        }
+    }
+
+    /// A hand-built `bplist00` whose only object is an ASCII `Str` with an invalid UTF-8 byte --
+    /// the writer only ever emits valid UTF-8, so this is crafted directly.
+    #[test]
+    fn from_bytes_lossy_substitutes_invalid_string_bytes() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"bplist00");
+        // object 0: ASCII_STR, length 1, one invalid byte.
+        bytes.push((marker::ASCII_STR << 4) | 0b0001);
+        bytes.push(0xFF);
+        bytes.push(8); // offset table: one entry pointing at object 0's offset.
+        bytes.extend_from_slice(&[0u8; 6]);
+        bytes.push(1);
+        bytes.push(1);
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        bytes.extend_from_slice(&0u64.to_be_bytes());
+        bytes.extend_from_slice(&10u64.to_be_bytes());
+
+        assert!(matches!(BPList::from_bytes(&bytes), Err(Error::EncodingError)));
+
+        match BPList::from_bytes_lossy(&bytes).unwrap() {
+            BPList::Str(s) => assert_eq!(s, "\u{FFFD}"),
+            other => panic!("expected a string, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Wraps a `Read + Seek` source and tracks the current byte offset, so a decoding error can
+/// report exactly where in the stream it happened.
+pub struct PosReader<R> {
+    inner: R,
+    pos: u64,
+}
+
+impl<R: Read + Seek> PosReader<R> {
+    pub fn new(mut inner: R) -> io::Result<PosReader<R>> {
+        let pos = inner.seek(SeekFrom::Current(0))?;
+        Ok(PosReader { inner, pos })
+    }
+
+    /// The current byte offset into the wrapped source.
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+}
+
+impl<R: Read> Read for PosReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for PosReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = self.inner.seek(pos)?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn pos_tracks_reads_and_seeks() {
+        let mut reader = PosReader::new(Cursor::new(vec![0u8; 16])).unwrap();
+        assert_eq!(reader.pos(), 0);
+
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.pos(), 4);
+
+        reader.seek(SeekFrom::Start(10)).unwrap();
+        assert_eq!(reader.pos(), 10);
+    }
+}
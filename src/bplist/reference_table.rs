@@ -1,23 +1,22 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
-use std::fs::File;
 use std::io::Read;
 use std::ops::Index;
 
-use crate::result::Result;
-use crate::trailer::Trailer;
-use crate::util;
+use super::result::Result;
+use super::trailer::Trailer;
+use super::util;
 
 pub struct ReferenceTable(HashMap<u64, u64>);
 
 impl ReferenceTable {
-    pub fn load(file: &mut File, trailer: &Trailer) -> Result<ReferenceTable> {
+    pub fn load<R: Read>(file: &mut R, trailer: &Trailer) -> Result<ReferenceTable> {
         let mut reference_table = ReferenceTable(HashMap::new());
 
         for i in 0..trailer.num_objects {
             let mut buf = vec![0; trailer.offset_table_offset_size as usize];
             file.read_exact(buf.as_mut_slice())?;
-            reference_table.0.insert(i, util::from_be_bytes(&buf));
+            reference_table.0.insert(i, util::from_be_bytes_unsigned(&buf)?);
         }
 
         Ok(reference_table)
@@ -51,4 +50,3 @@ impl Index<&'_ u64> for ReferenceTable {
         &self.0[&idx]
     }
 }
-
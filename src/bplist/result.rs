@@ -0,0 +1,47 @@
+use std::io;
+
+/// A minimal classification of I/O failures. `Error` carries this instead of `std::io::Error`
+/// directly so the type (and anything matching on it) doesn't depend on the concrete `io::Error`
+/// representation -- callers only ever need to distinguish "ran out of bytes" from everything
+/// else.
+#[derive(Debug)]
+pub enum IoErrorKind {
+    UnexpectedEof,
+    Other,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    IOError(IoErrorKind),
+    EncodingError,
+    InvalidFormat(&'static str),
+    NotFound,
+
+    /// The byte at `offset` had a marker high nibble this parser doesn't recognize.
+    BadMarker { offset: u64, marker: u8 },
+    /// Ran out of bytes at `offset` while trying to read `needed` more.
+    UnexpectedEof { offset: u64, needed: usize },
+    /// The length-encoding byte width read at `offset` doesn't fit this format.
+    BadLength { offset: u64, width: usize },
+    /// The UTF-16 string payload starting at `offset` isn't valid UTF-16.
+    InvalidUtf16 { offset: u64 },
+    /// An objref/keyref read at `offset` pointed outside the object table.
+    RefOutOfRange { offset: u64, objref: u64 },
+    /// A streaming decoder's source ran out of bytes partway through a multi-byte/multi-unit
+    /// codepoint, rather than cleanly between codepoints.
+    StreamEof,
+    /// A streaming decoder read a complete but invalid byte/unit sequence for its encoding.
+    InvalidStreamBytes,
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        let kind = match error.kind() {
+            io::ErrorKind::UnexpectedEof => IoErrorKind::UnexpectedEof,
+            _ => IoErrorKind::Other,
+        };
+        Error::IOError(kind)
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
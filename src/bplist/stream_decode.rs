@@ -0,0 +1,138 @@
+//! Incremental, low-memory decoders that yield one `char` at a time from a `Read` source instead
+//! of requiring the whole string already sitting in a `&[u8]` buffer, the way `util::as_utf8`/
+//! `util::as_utf16be` do. This is groundwork for a future streaming object-table parser that can
+//! extract a large `Str`/`Data` field without first buffering the whole thing in memory.
+
+use std::io::Read;
+use std::str;
+
+use super::result::{Error, Result};
+
+/// Decodes UTF-8 one codepoint at a time out of `R`, reading at most 4 bytes per codepoint.
+pub struct Utf8Chars<R> {
+    reader: R,
+}
+
+impl<R: Read> Utf8Chars<R> {
+    pub fn new(reader: R) -> Utf8Chars<R> {
+        Utf8Chars { reader }
+    }
+}
+
+impl<R: Read> Iterator for Utf8Chars<R> {
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Result<char>> {
+        let mut buf = [0u8; 4];
+        let mut len = 0usize;
+
+        loop {
+            let mut byte = [0u8];
+            match self.reader.read(&mut byte) {
+                Ok(0) if len == 0 => return None,
+                Ok(0) => return Some(Err(Error::StreamEof)),
+                Ok(_) => {
+                    buf[len] = byte[0];
+                    len += 1;
+                }
+                Err(e) => return Some(Err(e.into())),
+            }
+
+            match str::from_utf8(&buf[..len]) {
+                Ok(s) => return Some(Ok(s.chars().next().unwrap())),
+                Err(e) if e.error_len().is_none() && len < 4 => continue,
+                Err(_) => return Some(Err(Error::InvalidStreamBytes)),
+            }
+        }
+    }
+}
+
+/// Decodes big-endian UTF-16 one codepoint at a time out of `R`, reading one or two 2-byte units
+/// per codepoint (the second only for a surrogate pair).
+pub struct Utf16BeChars<R> {
+    reader: R,
+}
+
+impl<R: Read> Utf16BeChars<R> {
+    pub fn new(reader: R) -> Utf16BeChars<R> {
+        Utf16BeChars { reader }
+    }
+
+    fn read_unit(&mut self) -> Result<Option<u16>> {
+        let mut high = [0u8];
+        if self.reader.read(&mut high)? == 0 {
+            return Ok(None);
+        }
+        let mut low = [0u8];
+        if self.reader.read(&mut low)? == 0 {
+            return Err(Error::StreamEof);
+        }
+        Ok(Some(u16::from_be_bytes([high[0], low[0]])))
+    }
+}
+
+impl<R: Read> Iterator for Utf16BeChars<R> {
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Result<char>> {
+        let first = match self.read_unit() {
+            Ok(Some(unit)) => unit,
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+
+        if !(0xD800..=0xDBFF).contains(&first) {
+            return Some(char::from_u32(first as u32).ok_or(Error::InvalidStreamBytes));
+        }
+
+        let second = match self.read_unit() {
+            Ok(Some(unit)) => unit,
+            Ok(None) => return Some(Err(Error::StreamEof)),
+            Err(e) => return Some(Err(e)),
+        };
+        if !(0xDC00..=0xDFFF).contains(&second) {
+            return Some(Err(Error::InvalidStreamBytes));
+        }
+
+        let codepoint =
+            0x10000 + ((first as u32 - 0xD800) << 10) + (second as u32 - 0xDC00);
+        Some(char::from_u32(codepoint).ok_or(Error::InvalidStreamBytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn utf8_chars_yields_each_codepoint() {
+        let chars: Result<Vec<char>> = Utf8Chars::new(Cursor::new("héllo".as_bytes())).collect();
+        assert_eq!(chars.unwrap(), vec!['h', 'é', 'l', 'l', 'o']);
+    }
+
+    #[test]
+    fn utf8_chars_reports_eof_mid_codepoint() {
+        // 'é' is 2 bytes (0xC3 0xA9); truncate after the first.
+        let mut chars = Utf8Chars::new(Cursor::new(vec![0xC3]));
+        assert!(matches!(chars.next(), Some(Err(Error::StreamEof))));
+    }
+
+    #[test]
+    fn utf16be_chars_yields_surrogate_pairs() {
+        let mut bytes = Vec::new();
+        for unit in "h😀i".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_be_bytes());
+        }
+        let chars: Result<Vec<char>> = Utf16BeChars::new(Cursor::new(bytes)).collect();
+        assert_eq!(chars.unwrap(), vec!['h', '😀', 'i']);
+    }
+
+    #[test]
+    fn utf16be_chars_reports_eof_after_lone_high_surrogate() {
+        let bytes = [0xD8, 0x00]; // a high surrogate with no following low surrogate.
+        let mut chars = Utf16BeChars::new(Cursor::new(bytes));
+        assert!(matches!(chars.next(), Some(Err(Error::StreamEof))));
+    }
+}
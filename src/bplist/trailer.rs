@@ -1,7 +1,6 @@
-use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
 
-use crate::result::Result;
+use super::result::Result;
 
 pub struct Trailer {
     pub offset_table_offset_size: u8,
@@ -12,7 +11,7 @@ pub struct Trailer {
 }
 
 impl Trailer {
-    pub fn load(file: &mut File) -> Result<Trailer> {
+    pub fn load<R: Read>(file: &mut R) -> Result<Trailer> {
         let mut buf = [0; 8];
         file.read_exact(&mut buf[0..6])?;
 
@@ -39,4 +38,16 @@ impl Trailer {
             offset_table_start,
         })
     }
+
+    /// Writes the 32-byte trailer in the same field order `load` reads it back in: 6 unused bytes,
+    /// then the two width bytes, then `num_objects`/`top_object_offset`/`offset_table_start` as
+    /// big-endian `u64`s.
+    pub fn write<W: Write>(&self, out: &mut W) -> Result<()> {
+        out.write_all(&[0u8; 6])?;
+        out.write_all(&[self.offset_table_offset_size, self.object_ref_size])?;
+        out.write_all(&self.num_objects.to_be_bytes())?;
+        out.write_all(&self.top_object_offset.to_be_bytes())?;
+        out.write_all(&self.offset_table_start.to_be_bytes())?;
+        Ok(())
+    }
 }
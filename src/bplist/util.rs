@@ -2,16 +2,52 @@ use std::str;
 
 use super::result::{Error, Result};
 
-pub fn from_be_bytes(bytes: &Vec<u8>) -> u64 {
+/// Accumulates up to 8 big-endian bytes into an unsigned `u64`, for objrefs/keyrefs and offset
+/// table entries, which the format never widens past 8 bytes.
+pub fn from_be_bytes_unsigned(bytes: &[u8]) -> Result<u64> {
     if bytes.len() > 8 {
-        panic!("oops better error handling here");
+        return Err(Error::InvalidFormat(
+            "unsigned big-endian value wider than 8 bytes",
+        ));
     }
 
     let mut register = 0u64;
     for byte in bytes.into_iter() {
         register = (register << 8) | *byte as u64;
     }
-    register
+    Ok(register)
+}
+
+/// Accumulates up to 16 big-endian bytes into a signed `i128`, two's-complementing the result when
+/// the top bit of the accumulated width is set -- the interpretation bplist's 8-byte and 16-byte
+/// `Int` objects use (unlike the narrower unsigned widths `from_be_bytes_unsigned` covers).
+pub fn from_be_bytes_signed(bytes: &[u8]) -> Result<i128> {
+    if bytes.len() > 16 {
+        return Err(Error::InvalidFormat(
+            "signed big-endian value wider than 16 bytes",
+        ));
+    }
+    if bytes.is_empty() {
+        return Ok(0);
+    }
+
+    let mut magnitude = 0u128;
+    for byte in bytes.into_iter() {
+        magnitude = (magnitude << 8) | *byte as u128;
+    }
+
+    let bit_width = bytes.len() * 8;
+    if bit_width == 128 {
+        // the full 128 bits already hold the two's-complement bit pattern `i128` expects.
+        return Ok(magnitude as i128);
+    }
+
+    let sign_bit = 1u128 << (bit_width - 1);
+    if magnitude & sign_bit != 0 {
+        Ok(magnitude as i128 - (1i128 << bit_width))
+    } else {
+        Ok(magnitude as i128)
+    }
 }
 
 pub fn as_utf8(buf: &[u8]) -> Result<&str> {
@@ -21,15 +57,123 @@ pub fn as_utf8(buf: &[u8]) -> Result<&str> {
     }
 }
 
-pub fn as_utf16(buf: &[u8]) -> Result<String> {
+/// Like `as_utf8`, but invalid sequences are replaced with U+FFFD instead of failing the decode.
+pub fn as_utf8_lossy(buf: &[u8]) -> String {
+    String::from_utf8_lossy(buf).into_owned()
+}
+
+/// Decodes big-endian UTF-16, the byte order the bplist format itself uses for `UTF16_STR`
+/// objects.
+pub fn as_utf16be(buf: &[u8]) -> Result<String> {
     if buf.len() % 2 != 0 {
         return Err(Error::InvalidFormat("utf16 buf must be even length"));
     }
 
-    let mut combined_buf = vec![0; buf.len() / 2];
-    for i in 0..buf.len() / 2 {
+    let units: Vec<u16> = buf
+        .chunks_exact(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16(&units).map_err(|_| Error::EncodingError)
+}
+
+/// Decodes little-endian UTF-16, for strings sourced from a little-endian platform rather than
+/// bplist's own object table encoding.
+pub fn as_utf16le(buf: &[u8]) -> Result<String> {
+    if buf.len() % 2 != 0 {
+        return Err(Error::InvalidFormat("utf16 buf must be even length"));
+    }
+
+    let units: Vec<u16> = buf
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16(&units).map_err(|_| Error::EncodingError)
+}
+
+pub fn as_utf16(buf: &[u8]) -> Result<String> {
+    as_utf16be(buf)
+}
+
+/// Sniffs a leading byte-order mark -- `FE FF` for big-endian, `FF FE` for little-endian -- and
+/// strips it before decoding with the matching endianness, falling back to big-endian when no BOM
+/// is present.
+pub fn decode_utf16_with_bom(buf: &[u8]) -> Result<String> {
+    match buf {
+        [0xFE, 0xFF, rest @ ..] => as_utf16be(rest),
+        [0xFF, 0xFE, rest @ ..] => as_utf16le(rest),
+        _ => as_utf16be(buf),
+    }
+}
+
+/// Like `as_utf16`, but invalid code units are replaced with U+FFFD instead of failing the
+/// decode, and a trailing odd byte (not enough left for one more 16-bit unit) becomes a single
+/// trailing U+FFFD rather than rejecting the whole buffer.
+pub fn as_utf16_lossy(buf: &[u8]) -> String {
+    let pairs = buf.len() / 2;
+    let mut combined_buf = vec![0; pairs];
+    for i in 0..pairs {
         combined_buf[i] = ((buf[2 * i] as u16) << 8) | (buf[2 * i + 1] as u16);
     }
 
-    String::from_utf16(&combined_buf).map_err(|_| Error::EncodingError)
+    let mut s = String::from_utf16_lossy(&combined_buf);
+    if buf.len() % 2 != 0 {
+        s.push('\u{FFFD}');
+    }
+    s
+}
+
+/// Decodes `buf` as a legacy single-byte or 8-bit charset (e.g. `encoding_rs::WINDOWS_1252`,
+/// `encoding_rs::ISO_8859_2`) rather than the ASCII/UTF-16BE bplist itself uses for `Str` objects.
+/// Real-world plists produced by older tooling occasionally carry 8-bit string data that isn't
+/// valid UTF-8, and this lets a caller who knows (or can guess) the source charset recover it
+/// instead of the parse failing outright. Returns the decoded text alongside whether `encoding_rs`
+/// had to substitute any replacement characters along the way, so the caller can judge how much to
+/// trust the result.
+#[cfg(feature = "legacy-charset")]
+pub fn as_legacy_charset(buf: &[u8], encoding: &'static encoding_rs::Encoding) -> (String, bool) {
+    let (text, _, had_replacements) = encoding.decode(buf);
+    (text.into_owned(), had_replacements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn as_utf16_is_big_endian() {
+        // "hi" as big-endian UTF-16 code units.
+        let buf = [0x00, 0x68, 0x00, 0x69];
+        assert_eq!(as_utf16(&buf).unwrap(), "hi");
+    }
+
+    #[test]
+    fn as_utf16le_reads_little_endian_units() {
+        let buf = [0x68, 0x00, 0x69, 0x00];
+        assert_eq!(as_utf16le(&buf).unwrap(), "hi");
+    }
+
+    #[test]
+    fn as_utf8_lossy_substitutes_invalid_bytes() {
+        let buf = [b'h', b'i', 0xFF];
+        assert_eq!(as_utf8_lossy(&buf), "hi\u{FFFD}");
+    }
+
+    #[test]
+    fn as_utf16_lossy_handles_unpaired_surrogates_and_trailing_byte() {
+        // an unpaired high surrogate (0xD800) followed by one trailing byte.
+        let buf = [0xD8, 0x00, 0xFF];
+        assert_eq!(as_utf16_lossy(&buf), "\u{FFFD}\u{FFFD}");
+    }
+
+    #[test]
+    fn decode_utf16_with_bom_sniffs_either_endianness() {
+        let be = [0xFE, 0xFF, 0x00, 0x68, 0x00, 0x69];
+        assert_eq!(decode_utf16_with_bom(&be).unwrap(), "hi");
+
+        let le = [0xFF, 0xFE, 0x68, 0x00, 0x69, 0x00];
+        assert_eq!(decode_utf16_with_bom(&le).unwrap(), "hi");
+
+        let no_bom = [0x00, 0x68, 0x00, 0x69];
+        assert_eq!(decode_utf16_with_bom(&no_bom).unwrap(), "hi");
+    }
 }
@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::io::{Seek, Write};
+
+use super::result::Result;
+use super::trailer::Trailer;
+use super::BPList;
+
+/// Mirrors the reader's `Trailer::load`/`Trailer::write` split: anything that knows how to turn
+/// itself into bytes for the object table implements this, rather than `write` inlining every
+/// format. `object_ref_size` is the width every composite `Entry` needs to size its refs but
+/// `Trailer` doesn't, so it's threaded through uniformly rather than adding a second trait method.
+pub trait ToWriter {
+    fn write_to<W: Write>(&self, object_ref_size: usize, out: &mut W) -> Result<()>;
+}
+
+impl ToWriter for Trailer {
+    fn write_to<W: Write>(&self, _object_ref_size: usize, out: &mut W) -> Result<()> {
+        self.write(out)
+    }
+}
+
+impl ToWriter for Entry {
+    fn write_to<W: Write>(&self, object_ref_size: usize, out: &mut W) -> Result<()> {
+        match self {
+            Entry::Scalar(value) => encode_scalar(value, out)?,
+            Entry::Array(items) => {
+                write_marker_and_length(marker::ARRAY, items.len() as u64, out)?;
+                for &idx in items {
+                    out.write_all(&be_bytes(idx, object_ref_size))?;
+                }
+            }
+            Entry::Set(items) => {
+                write_marker_and_length(marker::SET, items.len() as u64, out)?;
+                for &idx in items {
+                    out.write_all(&be_bytes(idx, object_ref_size))?;
+                }
+            }
+            Entry::Dict(pairs) => {
+                write_marker_and_length(marker::DICT, pairs.len() as u64, out)?;
+                for (key, _) in pairs {
+                    out.write_all(&be_bytes(*key, object_ref_size))?;
+                }
+                for (_, value) in pairs {
+                    out.write_all(&be_bytes(*value, object_ref_size))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serializes `value` back into the `bplist00` binary format: walks the tree once to flatten it
+/// into a deduplicated object table, then writes the header, object table, offset table, and
+/// trailer in that order.
+pub fn write<W: Write + Seek>(value: &BPList, w: &mut W) -> Result<()> {
+    let mut entries = Vec::new();
+    let mut dedup = HashMap::new();
+    let root_index = collect(value, &mut entries, &mut dedup);
+
+    let num_objects = entries.len() as u64;
+    let object_ref_size = min_byte_width(num_objects.saturating_sub(1));
+
+    // the reader trusts that the top-level object comes immediately after the magic, so it must
+    // be the first thing emitted even though it was the last entry assigned (children are always
+    // collected -- and thus indexed -- before their parents).
+    let mut emission_order = Vec::with_capacity(entries.len());
+    emission_order.push(root_index);
+    for idx in 0..num_objects {
+        if idx != root_index {
+            emission_order.push(idx);
+        }
+    }
+
+    let mut object_table = Vec::new();
+    let mut offsets = vec![0u64; entries.len()];
+    for idx in emission_order {
+        offsets[idx as usize] = 8 + object_table.len() as u64;
+        entries[idx as usize].write_to(object_ref_size, &mut object_table)?;
+    }
+
+    let offset_table_start = 8 + object_table.len() as u64;
+    let offset_size = min_byte_width(offsets.iter().copied().max().unwrap_or(0));
+
+    let trailer = Trailer {
+        offset_table_offset_size: offset_size as u8,
+        object_ref_size: object_ref_size as u8,
+        num_objects,
+        top_object_offset: root_index,
+        offset_table_start,
+    };
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"bplist00");
+    out.extend_from_slice(&object_table);
+
+    for offset in offsets.iter() {
+        out.extend_from_slice(&be_bytes(*offset, offset_size));
+    }
+
+    trailer.write_to(object_ref_size, &mut out)?;
+
+    w.write_all(&out)?;
+    Ok(())
+}
+
+/// A `BPList` node flattened into the object table: composite nodes hold indices into the table
+/// rather than nesting their children directly.
+enum Entry {
+    Scalar(BPList),
+    Array(Vec<u64>),
+    Set(Vec<u64>),
+    Dict(Vec<(u64, u64)>),
+}
+
+/// Primitive objects that Apple's writer collapses to a single shared table entry when they
+/// compare equal; `Real`/`Date`/`Array`/`Dict` are always written out as distinct entries.
+#[derive(PartialEq, Eq, Hash)]
+enum DedupKey {
+    Null,
+    Bool(bool),
+    Filler,
+    Int(i128),
+    Data(Vec<u8>),
+    Str(String),
+    UID(Vec<u8>),
+}
+
+fn dedup_key(value: &BPList) -> Option<DedupKey> {
+    match value {
+        BPList::Null => Some(DedupKey::Null),
+        BPList::Bool(b) => Some(DedupKey::Bool(*b)),
+        BPList::Filler => Some(DedupKey::Filler),
+        BPList::Int(i) => Some(DedupKey::Int(*i)),
+        BPList::Data(d) => Some(DedupKey::Data(d.clone())),
+        BPList::Str(s) => Some(DedupKey::Str(s.clone())),
+        BPList::UID(u) => Some(DedupKey::UID(u.clone())),
+        BPList::Real(_) | BPList::Date(_) | BPList::Array(_) | BPList::Set(_) | BPList::Dict(_) => {
+            None
+        }
+    }
+}
+
+/// Flattens `value` into `entries`, returning its object index. Children are always collected
+/// (and thus assigned a lower index) before the composite that references them.
+fn collect(value: &BPList, entries: &mut Vec<Entry>, dedup: &mut HashMap<DedupKey, u64>) -> u64 {
+    if let Some(key) = dedup_key(value) {
+        if let Some(&idx) = dedup.get(&key) {
+            return idx;
+        }
+    }
+
+    match value {
+        BPList::Array(items) => {
+            let child_idxs: Vec<u64> = items
+                .iter()
+                .map(|item| collect(item, entries, dedup))
+                .collect();
+            push_entry(entries, Entry::Array(child_idxs))
+        }
+        BPList::Set(items) => {
+            let child_idxs: Vec<u64> = items
+                .iter()
+                .map(|item| collect(item, entries, dedup))
+                .collect();
+            push_entry(entries, Entry::Set(child_idxs))
+        }
+        BPList::Dict(fields) => {
+            let pairs: Vec<(u64, u64)> = fields
+                .iter()
+                .map(|(k, v)| (collect(k, entries, dedup), collect(v, entries, dedup)))
+                .collect();
+            push_entry(entries, Entry::Dict(pairs))
+        }
+        other => {
+            let idx = push_entry(entries, Entry::Scalar(other.clone()));
+            if let Some(key) = dedup_key(other) {
+                dedup.insert(key, idx);
+            }
+            idx
+        }
+    }
+}
+
+fn push_entry(entries: &mut Vec<Entry>, entry: Entry) -> u64 {
+    let idx = entries.len() as u64;
+    entries.push(entry);
+    idx
+}
+
+fn encode_scalar<W: Write>(value: &BPList, out: &mut W) -> Result<()> {
+    match value {
+        BPList::Null => out.write_all(&[0b0000_0000])?,
+        BPList::Bool(false) => out.write_all(&[0b0000_1000])?,
+        BPList::Bool(true) => out.write_all(&[0b0000_1001])?,
+        BPList::Filler => out.write_all(&[0b0000_1111])?,
+        BPList::Int(n) => encode_int(*n, out)?,
+        BPList::Real(f) => {
+            out.write_all(&[(marker::REAL << 4) | 0b0011])?;
+            out.write_all(&f.to_be_bytes())?;
+        }
+        BPList::Date(seconds) => {
+            out.write_all(&[(marker::DATE << 4) | 0b0011])?;
+            out.write_all(&seconds.to_be_bytes())?;
+        }
+        BPList::Data(bytes) => {
+            write_marker_and_length(marker::DATA, bytes.len() as u64, out)?;
+            out.write_all(bytes)?;
+        }
+        BPList::Str(s) if s.is_ascii() => {
+            write_marker_and_length(marker::ASCII_STR, s.len() as u64, out)?;
+            out.write_all(s.as_bytes())?;
+        }
+        BPList::Str(s) => {
+            let units: Vec<u16> = s.encode_utf16().collect();
+            write_marker_and_length(marker::UTF16_STR, units.len() as u64, out)?;
+            for unit in units {
+                out.write_all(&unit.to_be_bytes())?;
+            }
+        }
+        BPList::UID(bytes) => {
+            out.write_all(&[(marker::UID << 4) | (bytes.len() as u8 - 1)])?;
+            out.write_all(bytes)?;
+        }
+        BPList::Array(_) | BPList::Set(_) | BPList::Dict(_) => {
+            unreachable!("arrays, sets, and dicts are encoded via Entry::Array/Entry::Set/Entry::Dict")
+        }
+    }
+    Ok(())
+}
+
+/// Writes a marker byte for `marker_high`, inlining `length` directly when it fits in the low
+/// nibble, or escaping to `0b1111` followed by an inline int object otherwise.
+fn write_marker_and_length<W: Write>(marker_high: u8, length: u64, out: &mut W) -> Result<()> {
+    if length < 0b1111 {
+        out.write_all(&[(marker_high << 4) | (length as u8)])?;
+    } else {
+        out.write_all(&[(marker_high << 4) | 0b1111])?;
+        encode_int(length as i128, out)?;
+    }
+    Ok(())
+}
+
+fn encode_int<W: Write>(n: i128, out: &mut W) -> Result<()> {
+    let width = int_width(n);
+    out.write_all(&[(marker::INT << 4) | width.trailing_zeros() as u8])?;
+    if width == 16 {
+        out.write_all(&n.to_be_bytes())?;
+    } else if width == 8 {
+        out.write_all(&(n as i64).to_be_bytes())?;
+    } else {
+        out.write_all(&be_bytes(n as u64, width))?;
+    }
+    Ok(())
+}
+
+/// The byte width (a power of two, as `marker::INT` expects) needed to hold `n`. Negative values
+/// take the full 8 bytes so their two's-complement bit pattern round-trips exactly, unless they
+/// overflow `i64`, in which case they need the full 16-byte two's-complement form. Likewise,
+/// positive values beyond `i64::MAX` (but still representable as an unsigned 64-bit magnitude,
+/// or wider) need 16 bytes so a signed 8-byte read-back doesn't misinterpret them as negative.
+fn int_width(n: i128) -> usize {
+    if n < 0 {
+        if n >= i64::MIN as i128 {
+            8
+        } else {
+            16
+        }
+    } else if n <= 0xFF {
+        1
+    } else if n <= 0xFFFF {
+        2
+    } else if n <= 0xFFFF_FFFF {
+        4
+    } else if n <= i64::MAX as i128 {
+        8
+    } else {
+        16
+    }
+}
+
+fn min_byte_width(max_value: u64) -> usize {
+    if max_value <= 0xFF {
+        1
+    } else if max_value <= 0xFFFF {
+        2
+    } else if max_value <= 0xFFFF_FFFF {
+        4
+    } else {
+        8
+    }
+}
+
+fn be_bytes(value: u64, width: usize) -> Vec<u8> {
+    value.to_be_bytes()[(8 - width)..].to_vec()
+}
+
+mod marker {
+    pub const INT: u8 = 1;
+    pub const REAL: u8 = 2;
+    pub const DATE: u8 = 3;
+    pub const DATA: u8 = 4;
+    pub const ASCII_STR: u8 = 5;
+    pub const UTF16_STR: u8 = 6;
+    pub const UID: u8 = 8;
+    pub const ARRAY: u8 = 10;
+    pub const SET: u8 = 12;
+    pub const DICT: u8 = 13;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    use super::{BPList, Trailer};
+
+    #[test]
+    fn round_trip_dedups_equal_scalars() {
+        let value = BPList::Array(vec![
+            Rc::new(BPList::Str("x".to_owned())),
+            Rc::new(BPList::Str("x".to_owned())),
+            Rc::new(BPList::Str("x".to_owned())),
+        ]);
+        let bytes = value.to_bytes().unwrap();
+
+        let mut trailer_cursor = Cursor::new(&bytes[bytes.len() - 32..]);
+        let trailer = Trailer::load(&mut trailer_cursor).unwrap();
+        // the array plus one shared "x" entry -- not three separate "x" entries.
+        assert_eq!(trailer.num_objects, 2);
+
+        match BPList::from_bytes(&bytes).unwrap() {
+            BPList::Array(items) => {
+                assert_eq!(items.len(), 3);
+                for item in items {
+                    assert_eq!(*item, BPList::Str("x".to_owned()));
+                }
+            }
+            other => panic!("expected an array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn offset_table_width_grows_with_object_count() {
+        let items: Vec<Rc<BPList>> = (0..300).map(|i| Rc::new(BPList::Int(i as i128))).collect();
+        let value = BPList::Array(items);
+        let bytes = value.to_bytes().unwrap();
+
+        let mut trailer_cursor = Cursor::new(&bytes[bytes.len() - 32..]);
+        let trailer = Trailer::load(&mut trailer_cursor).unwrap();
+        // 300 distinct ints plus the array itself pushes object table offsets past 255 bytes.
+        assert_eq!(trailer.offset_table_offset_size, 2);
+
+        match BPList::from_bytes(&bytes).unwrap() {
+            BPList::Array(items) => assert_eq!(items.len(), 300),
+            other => panic!("expected an array, got {:?}", other),
+        }
+    }
+}
@@ -1,19 +1,26 @@
 use super::bplist::{BPList, Result};
+use super::keyed_archive;
 
 pub fn explore(bplist: BPList) -> Result<()> {
     // prints out all of the text messages
-    if let BPList::Array(items) = bplist.gets("$objects")? {
-        for item in items.into_iter() {
-            let class = match item.gets("$class") {
-                Err(_) => continue,
-                Ok(class) => class,
-            };
+    let decoded = keyed_archive::decode(&bplist)?;
+    print_strings(&decoded);
+    Ok(())
+}
 
-            if class == &BPList::UID(vec![18]) {
-                println!("{:?}", item.gets("NS.string")?);
+fn print_strings(value: &BPList) {
+    match value {
+        BPList::Str(s) => println!("{:?}", s),
+        BPList::Array(items) => {
+            for item in items.iter() {
+                print_strings(item);
+            }
+        }
+        BPList::Dict(fields) => {
+            for (_, value) in fields.iter() {
+                print_strings(value);
             }
         }
+        _ => {}
     }
-
-    Ok(())
 }
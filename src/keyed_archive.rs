@@ -0,0 +1,180 @@
+//! Decodes `NSKeyedArchiver`-encoded `BPList` trees into ordinary values.
+//!
+//! `NSKeyedArchiver` stores its object graph as a flat `$objects` array and threads references
+//! between entries as `UID`s (a big-endian index into that array) rather than nesting objects
+//! directly, so a caller can't walk the archive without first resolving those UIDs and
+//! reconstructing `NSDictionary`/`NSArray`/`NSString` from their `NS.keys`/`NS.objects`/
+//! `NS.string` fields. `decode` does that once, up front, and hands back a plain
+//! `BPList::Dict`/`Array`/`Str` tree.
+
+use std::rc::Rc;
+
+use crate::bplist::{BPList, Error, Result};
+
+const ARCHIVER_KEY: &str = "$archiver";
+const NSKEYEDARCHIVER: &str = "NSKeyedArchiver";
+
+/// Validates that `archive` is an `NSKeyedArchiver` plist, then fully dereferences its `$top`
+/// root object, following `UID`s into `$objects` and reconstructing `NS.keys`/`NS.objects`/
+/// `NS.string`-shaped entries into ordinary `Dict`/`Array`/`Str` values.
+pub fn decode(archive: &BPList) -> Result<BPList> {
+    let archiver = archive.gets(ARCHIVER_KEY)?;
+    if archiver != &BPList::Str(NSKEYEDARCHIVER.to_owned()) {
+        return Err(Error::InvalidFormat("not an NSKeyedArchiver archive"));
+    }
+
+    let objects = as_array(archive.gets("$objects")?)?;
+    let root = archive.gets("$top")?.gets("root")?;
+
+    let mut resolving = Vec::new();
+    resolve(objects, root, &mut resolving)
+}
+
+/// Resolves a value to its fully-decoded form: a `UID` is followed into `$objects` (detecting
+/// reference cycles along the way), anything else is returned as-is.
+fn resolve(objects: &[Rc<BPList>], value: &BPList, resolving: &mut Vec<u64>) -> Result<BPList> {
+    let uid = match value {
+        BPList::UID(bytes) => uid_index(bytes),
+        other => return Ok(other.clone()),
+    };
+
+    if resolving.contains(&uid) {
+        return Err(Error::InvalidFormat("recursive object reference"));
+    }
+    let obj = objects.get(uid as usize).ok_or(Error::NotFound)?;
+
+    resolving.push(uid);
+    let decoded = decode_object(objects, obj, resolving);
+    resolving.pop();
+    decoded
+}
+
+fn decode_object(
+    objects: &[Rc<BPList>],
+    obj: &BPList,
+    resolving: &mut Vec<u64>,
+) -> Result<BPList> {
+    let fields = match obj {
+        BPList::Dict(fields) => fields,
+        other => return Ok(other.clone()),
+    };
+
+    match class_name(objects, fields) {
+        Ok(classname) => match classname.as_str() {
+            "NSDictionary" | "NSMutableDictionary" => decode_ns_dictionary(objects, fields, resolving),
+            "NSArray" | "NSMutableArray" | "NSSet" | "NSMutableSet" => {
+                decode_ns_array(objects, fields, resolving)
+            }
+            "NSString" | "NSMutableString" => decode_ns_string(fields),
+            _ => decode_plain_dict(objects, fields, resolving),
+        },
+        Err(_) => decode_plain_dict(objects, fields, resolving),
+    }
+}
+
+/// Follows a dict's `$class` UID into `$objects` and reads the `$classname` chain off it.
+fn class_name(objects: &[Rc<BPList>], fields: &[(Rc<BPList>, Rc<BPList>)]) -> Result<String> {
+    let class_uid = field(fields, "$class").ok_or(Error::NotFound)?;
+    let class_idx = match class_uid {
+        BPList::UID(bytes) => uid_index(bytes),
+        _ => return Err(Error::InvalidFormat("$class is not a UID")),
+    };
+
+    let class_fields = match objects.get(class_idx as usize).map(|obj| obj.as_ref()) {
+        Some(BPList::Dict(fields)) => fields,
+        _ => return Err(Error::InvalidFormat("$class entry is not a dict")),
+    };
+
+    match field(class_fields, "$classname") {
+        Some(BPList::Str(name)) => Ok(name.clone()),
+        _ => Err(Error::InvalidFormat("$classname missing or not a string")),
+    }
+}
+
+fn decode_ns_string(fields: &[(Rc<BPList>, Rc<BPList>)]) -> Result<BPList> {
+    match field(fields, "NS.string") {
+        Some(BPList::Str(s)) => Ok(BPList::Str(s.clone())),
+        _ => Err(Error::InvalidFormat("NSString missing NS.string")),
+    }
+}
+
+fn decode_ns_array(
+    objects: &[Rc<BPList>],
+    fields: &[(Rc<BPList>, Rc<BPList>)],
+    resolving: &mut Vec<u64>,
+) -> Result<BPList> {
+    let items = match field(fields, "NS.objects") {
+        Some(BPList::Array(items)) => items,
+        _ => return Err(Error::InvalidFormat("NS collection missing NS.objects")),
+    };
+
+    let mut resolved = Vec::with_capacity(items.len());
+    for item in items.iter() {
+        resolved.push(Rc::new(resolve(objects, item, resolving)?));
+    }
+    Ok(BPList::Array(resolved))
+}
+
+fn decode_ns_dictionary(
+    objects: &[Rc<BPList>],
+    fields: &[(Rc<BPList>, Rc<BPList>)],
+    resolving: &mut Vec<u64>,
+) -> Result<BPList> {
+    let keys = match field(fields, "NS.keys") {
+        Some(BPList::Array(keys)) => keys,
+        _ => return Err(Error::InvalidFormat("NSDictionary missing NS.keys")),
+    };
+    let values = match field(fields, "NS.objects") {
+        Some(BPList::Array(values)) => values,
+        _ => return Err(Error::InvalidFormat("NSDictionary missing NS.objects")),
+    };
+    if keys.len() != values.len() {
+        return Err(Error::InvalidFormat(
+            "NSDictionary NS.keys/NS.objects length mismatch",
+        ));
+    }
+
+    let mut resolved = Vec::with_capacity(keys.len());
+    for (key, value) in keys.iter().zip(values.iter()) {
+        let key = resolve(objects, key, resolving)?;
+        let value = resolve(objects, value, resolving)?;
+        resolved.push((Rc::new(key), Rc::new(value)));
+    }
+    Ok(BPList::Dict(resolved))
+}
+
+fn decode_plain_dict(
+    objects: &[Rc<BPList>],
+    fields: &[(Rc<BPList>, Rc<BPList>)],
+    resolving: &mut Vec<u64>,
+) -> Result<BPList> {
+    let mut resolved = Vec::with_capacity(fields.len());
+    for (key, value) in fields.iter() {
+        let key = resolve(objects, key, resolving)?;
+        let value = resolve(objects, value, resolving)?;
+        resolved.push((Rc::new(key), Rc::new(value)));
+    }
+    Ok(BPList::Dict(resolved))
+}
+
+fn field<'a>(fields: &'a [(Rc<BPList>, Rc<BPList>)], key: &str) -> Option<&'a BPList> {
+    fields
+        .iter()
+        .find(|(k, _)| k.as_ref() == &BPList::Str(key.to_owned()))
+        .map(|(_, v)| v.as_ref())
+}
+
+fn as_array(value: &BPList) -> Result<&[Rc<BPList>]> {
+    match value {
+        BPList::Array(items) => Ok(items),
+        _ => Err(Error::InvalidFormat("$objects is not an array")),
+    }
+}
+
+fn uid_index(bytes: &[u8]) -> u64 {
+    let mut n = 0u64;
+    for byte in bytes {
+        n = (n << 8) | (*byte as u64);
+    }
+    n
+}
@@ -0,0 +1,3 @@
+pub mod bplist;
+pub mod imessage;
+pub mod keyed_archive;
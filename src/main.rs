@@ -1,11 +1,11 @@
 use std::fs::File;
 
-mod bplist;
-mod imessage;
+use bplist_rs::bplist::{self, BPList};
+use bplist_rs::imessage;
 
 fn main() -> bplist::Result<()> {
     let mut file = File::open("test.ichat")?;
-    let bplist = bplist::BPList::load(&mut file)?;
+    let bplist = BPList::load(&mut file)?;
 
     imessage::explore(bplist)?;
 